@@ -0,0 +1,87 @@
+//! C ABI扩展接口
+//!
+//! 与 [`crate::split_pdf_chapters`] 并列，这里补充带进度回调的多线程拆分版本，
+//! 供Go后端驱动进度条使用。
+
+use std::ffi::{c_char, CStr, CString};
+use std::os::raw::c_uint;
+
+use crate::{pdf, splitter, validator, ChapterDefinition, ProcessResult};
+
+/// 进度回调：每完成一个章节调用一次
+///
+/// * `processed` - 已完成的章节数
+/// * `total` - 章节总数
+/// * `current_title` - 刚完成的章节标题，仅在回调期间有效
+pub type ProgressCallback = extern "C" fn(processed: u32, total: u32, current_title: *const c_char);
+
+/// 多线程拆分PDF章节，每完成一个章节即通过 `progress_cb` 回调报告进度
+///
+/// `worker_count` 为 `0` 时使用系统默认可用并行度。任意工作线程中的错误都会被聚合进
+/// 返回的 [`ProcessResult`]，而不会越过FFI边界发生panic。
+///
+/// # Safety
+///
+/// 调用方必须保证 `input_path`、`output_dir` 指向以空字符结尾的有效C字符串，
+/// `chapters` 指向至少 `chapter_count` 个连续 [`ChapterDefinition`] 元素，且
+/// `progress_cb` 是一个在调用期间保持有效的合法函数指针。
+#[no_mangle]
+pub unsafe extern "C" fn split_pdf_chapters_with_progress(
+    input_path: *const c_char,
+    chapters: *const ChapterDefinition,
+    chapter_count: usize,
+    output_dir: *const c_char,
+    worker_count: c_uint,
+    progress_cb: ProgressCallback,
+) -> ProcessResult {
+    let input_path = match CStr::from_ptr(input_path).to_str() {
+        Ok(s) => s,
+        Err(_) => return error_result("无效的输入路径"),
+    };
+
+    let output_dir = match CStr::from_ptr(output_dir).to_str() {
+        Ok(s) => s,
+        Err(_) => return error_result("无效的输出目录"),
+    };
+
+    let chapters_slice = std::slice::from_raw_parts(chapters, chapter_count);
+
+    // 如果输入是URL，先下载到临时文件；_temp_guard离开作用域时自动清理
+    let (resolved_path, _temp_guard) = match pdf::resolve_input(input_path, &[]) {
+        Ok(resolved) => resolved,
+        Err(e) => return error_result(&e.to_string()),
+    };
+    let resolved_path_str = resolved_path.to_string_lossy().into_owned();
+
+    // 预检：与 `PdfSplitter::split_pdf` 共用同一套校验，拒绝损坏/加密异常或页码越界的输入，
+    // 否则只有旧的 split_pdf_chapters 受保护，这条带进度回调的路径会悄悄写出损坏输出
+    if let Err(e) = validator::preflight_chapters(&resolved_path_str, chapters_slice) {
+        return error_result(&e.to_string());
+    }
+
+    let result = splitter::split_into_files_with_progress(
+        &resolved_path_str,
+        chapters_slice,
+        output_dir,
+        worker_count as usize,
+        |processed, total, title| {
+            // 标题中的NUL字节无法放入C字符串，去掉后仍保证每个章节都回调一次
+            let c_title = CString::new(title.replace('\0', "")).unwrap_or_default();
+            progress_cb(processed, total, c_title.as_ptr());
+        },
+    );
+
+    match result {
+        Ok(result) => result,
+        Err(e) => error_result(&e.to_string()),
+    }
+}
+
+fn error_result(message: &str) -> ProcessResult {
+    ProcessResult {
+        success: false,
+        error_message: Some(message.to_string()),
+        files_processed: 0,
+        total_pages: 0,
+    }
+}
@@ -22,6 +22,16 @@ pub struct ChapterDefinition {
     pub end_page: u32,
 }
 
+/// 拆分输出的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OutputFormat {
+    /// 每个章节输出为一个PDF文件（默认）
+    #[default]
+    Pdf,
+    /// 每个章节输出为EPUB，供电子书阅读器使用
+    Epub,
+}
+
 /// 处理结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[repr(C)]
@@ -48,24 +58,61 @@ impl PdfSplitter {
     }
 
     /// 拆分PDF文件为多个章节
-    /// 
+    ///
     /// # 参数
-    /// 
-    /// * `input_path` - 输入PDF文件路径
+    ///
+    /// * `input_path` - 输入PDF文件路径，可以是本地路径，也可以是 `http(s)://` URL
+    ///   （会先下载到临时文件）
     /// * `chapters` - 章节定义列表
     /// * `output_dir` - 输出目录路径
-    /// 
+    /// * `format` - 输出格式：每章一个PDF，或每章一个EPUB
+    /// * `combine_epub` - 当 `format` 为 `Epub` 时，是否将所有章节合并为单个EPUB文件
+    ///   （而不是每章一个文件），对PDF格式无效
+    ///
     /// # 返回
-    /// 
+    ///
     /// 返回处理结果，包含成功状态和相关信息
     pub fn split_pdf(
         &self,
         input_path: &str,
         chapters: &[ChapterDefinition],
         output_dir: &str,
+        format: OutputFormat,
+        combine_epub: bool,
+    ) -> anyhow::Result<ProcessResult> {
+        // 如果输入是URL，先下载到临时文件；_temp_guard离开作用域时自动清理
+        let (resolved_path, _temp_guard) = pdf::resolve_input(input_path, &[])?;
+        let resolved_path_str = resolved_path.to_string_lossy().into_owned();
+        let input_path = resolved_path_str.as_str();
+
+        // 预检：拒绝损坏/加密异常或章节页码越界的输入，而不是产出空文件或损坏的输出
+        validator::preflight_chapters(input_path, chapters)?;
+
+        match format {
+            OutputFormat::Pdf => splitter::split_into_files(input_path, chapters, output_dir),
+            OutputFormat::Epub => {
+                splitter::split_into_epub(input_path, chapters, output_dir, combine_epub)
+            }
+        }
+    }
+
+    /// 按顺序合并多个PDF文件为一个文件
+    ///
+    /// 合并后的文档保持各输入文件原有的页面顺序，`total_pages` 为各文件页数之和。
+    pub fn merge_pdfs(&self, inputs: &[&str], output_path: &str) -> anyhow::Result<ProcessResult> {
+        splitter::merge_pdfs(inputs, output_path)
+    }
+
+    /// 从PDF中删除指定的（1-based）页面，生成一个新文件
+    ///
+    /// 实现方式是"保留除 `pages_to_drop` 之外的所有页"；写入前会校验所有页码均在文档范围内。
+    pub fn remove_pages(
+        &self,
+        input_path: &str,
+        pages_to_drop: &[u32],
+        output_path: &str,
     ) -> anyhow::Result<ProcessResult> {
-        // 实现将在后续任务中添加
-        todo!("PDF拆分功能将在后续任务中实现")
+        splitter::remove_pages(input_path, pages_to_drop, output_path)
     }
 }
 
@@ -77,48 +124,48 @@ impl Default for PdfSplitter {
 
 // FFI接口导出
 /// C兼容的PDF拆分函数
-/// 
+///
 /// 供Go后端通过FFI调用
+///
+/// # Safety
+///
+/// 调用方必须保证 `input_path`、`output_dir` 指向以空字符结尾的有效C字符串，
+/// 且 `chapters` 指向至少 `chapter_count` 个连续 [`ChapterDefinition`] 元素；
+/// 这些指针在调用期间必须保持有效。
 #[no_mangle]
-pub extern "C" fn split_pdf_chapters(
+pub unsafe extern "C" fn split_pdf_chapters(
     input_path: *const c_char,
     chapters: *const ChapterDefinition,
     chapter_count: usize,
     output_dir: *const c_char,
 ) -> ProcessResult {
     // 安全地转换C字符串
-    let input_path = unsafe {
-        match CStr::from_ptr(input_path).to_str() {
-            Ok(s) => s,
-            Err(_) => return ProcessResult {
-                success: false,
-                error_message: Some("无效的输入路径".to_string()),
-                files_processed: 0,
-                total_pages: 0,
-            },
-        }
+    let input_path = match CStr::from_ptr(input_path).to_str() {
+        Ok(s) => s,
+        Err(_) => return ProcessResult {
+            success: false,
+            error_message: Some("无效的输入路径".to_string()),
+            files_processed: 0,
+            total_pages: 0,
+        },
     };
 
-    let output_dir = unsafe {
-        match CStr::from_ptr(output_dir).to_str() {
-            Ok(s) => s,
-            Err(_) => return ProcessResult {
-                success: false,
-                error_message: Some("无效的输出目录".to_string()),
-                files_processed: 0,
-                total_pages: 0,
-            },
-        }
+    let output_dir = match CStr::from_ptr(output_dir).to_str() {
+        Ok(s) => s,
+        Err(_) => return ProcessResult {
+            success: false,
+            error_message: Some("无效的输出目录".to_string()),
+            files_processed: 0,
+            total_pages: 0,
+        },
     };
 
     // 转换章节定义数组
-    let chapters_slice = unsafe {
-        std::slice::from_raw_parts(chapters, chapter_count)
-    };
+    let chapters_slice = std::slice::from_raw_parts(chapters, chapter_count);
 
     // 创建拆分器并执行拆分
     let splitter = PdfSplitter::new();
-    match splitter.split_pdf(input_path, chapters_slice, output_dir) {
+    match splitter.split_pdf(input_path, chapters_slice, output_dir, OutputFormat::Pdf, false) {
         Ok(result) => result,
         Err(e) => ProcessResult {
             success: false,
@@ -2,11 +2,10 @@
 //! 
 //! 提供PDF文件拆分的命令行接口
 
-use clap::{Arg, Command};
-use pdf_processor::{PdfSplitter, ChapterDefinition, ProcessResult};
+use clap::{Arg, ArgAction, Command};
+use pdf_processor::{pdf, validator, ChapterDefinition, OutputFormat, PdfSplitter, ProcessResult};
 use serde_json;
 use std::fs;
-use std::path::Path;
 use tracing::{info, error, Level};
 use tracing_subscriber;
 
@@ -38,7 +37,8 @@ fn main() -> anyhow::Result<()> {
                         .long("chapters")
                         .value_name("FILE")
                         .help("章节定义JSON文件路径")
-                        .required(true)
+                        .required_unless_present("auto")
+                        .conflicts_with("auto")
                 )
                 .arg(
                     Arg::new("output")
@@ -48,6 +48,94 @@ fn main() -> anyhow::Result<()> {
                         .help("输出目录路径")
                         .required(true)
                 )
+                .arg(
+                    Arg::new("auto")
+                        .long("auto")
+                        .help("根据PDF大纲/书签自动检测章节，无需提供章节定义文件")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("输出格式: pdf 或 epub")
+                        .value_parser(["pdf", "epub"])
+                        .default_value("pdf")
+                )
+                .arg(
+                    Arg::new("combine-epub")
+                        .long("combine-epub")
+                        .help("仅在--format epub时有效：将所有章节合并为单个EPUB文件，而不是每章一个文件")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("headers")
+                        .long("header")
+                        .value_name("KEY=VALUE")
+                        .help("当--input为URL时使用的自定义请求头，可重复指定多次")
+                        .action(ArgAction::Append)
+                )
+        )
+        .subcommand(
+            Command::new("detect")
+                .about("检测PDF文档中的章节(基于大纲/书签)并打印为JSON")
+                .arg(
+                    Arg::new("input")
+                        .short('i')
+                        .long("input")
+                        .value_name("FILE")
+                        .help("输入PDF文件路径")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("按顺序合并多个PDF文件")
+                .arg(
+                    Arg::new("inputs")
+                        .short('i')
+                        .long("input")
+                        .value_name("FILE")
+                        .help("要合并的PDF文件路径，可重复指定多次，按给定顺序合并")
+                        .required(true)
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("合并后输出的PDF文件路径")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("remove")
+                .about("从PDF中删除指定页面")
+                .arg(
+                    Arg::new("input")
+                        .short('i')
+                        .long("input")
+                        .value_name("FILE")
+                        .help("输入PDF文件路径")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("pages")
+                        .short('p')
+                        .long("pages")
+                        .value_name("RANGES")
+                        .help("要删除的页码，支持范围语法，如 3-5,9,12-14")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("输出PDF文件路径")
+                        .required(true)
+                )
         )
         .subcommand(
             Command::new("validate")
@@ -60,26 +148,56 @@ fn main() -> anyhow::Result<()> {
                         .help("输入PDF文件路径")
                         .required(true)
                 )
+                .arg(
+                    Arg::new("headers")
+                        .long("header")
+                        .value_name("KEY=VALUE")
+                        .help("当--input为URL时使用的自定义请求头，可重复指定多次")
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("以JSON格式输出验证报告")
+                        .action(ArgAction::SetTrue)
+                )
         )
         .get_matches();
 
     match matches.subcommand() {
         Some(("split", sub_matches)) => {
-            let input_path = sub_matches.get_one::<String>("input").unwrap();
-            let chapters_path = sub_matches.get_one::<String>("chapters").unwrap();
+            let input = sub_matches.get_one::<String>("input").unwrap();
             let output_dir = sub_matches.get_one::<String>("output").unwrap();
+            let auto = sub_matches.get_flag("auto");
+            let combine_epub = sub_matches.get_flag("combine-epub");
+            let format = match sub_matches.get_one::<String>("format").map(String::as_str) {
+                Some("epub") => OutputFormat::Epub,
+                _ => OutputFormat::Pdf,
+            };
+            let headers = parse_headers(sub_matches.get_many::<String>("headers"))?;
+
+            info!("开始拆分PDF文件: {}", input);
+
+            // 如果输入是URL则下载到临时文件；_temp_guard离开作用域时自动清理
+            let (resolved_path, _temp_guard) = pdf::resolve_input(input, &headers)?;
+            let input_path = resolved_path.to_string_lossy().into_owned();
+            let input_path = input_path.as_str();
+
+            // 读取章节定义：自动检测或从JSON文件加载
+            let chapters = if auto {
+                info!("未提供章节定义文件，使用PDF大纲自动检测章节");
+                pdf::detect_chapters(input_path)?
+            } else {
+                let chapters_path = sub_matches.get_one::<String>("chapters").unwrap();
+                load_chapters_from_file(chapters_path)?
+            };
 
-            info!("开始拆分PDF文件: {}", input_path);
-            
-            // 读取章节定义
-            let chapters = load_chapters_from_file(chapters_path)?;
-            
             // 创建输出目录
             fs::create_dir_all(output_dir)?;
-            
+
             // 执行拆分
             let splitter = PdfSplitter::new();
-            match splitter.split_pdf(input_path, &chapters, output_dir) {
+            match splitter.split_pdf(input_path, &chapters, output_dir, format, combine_epub) {
                 Ok(result) => {
                     if result.success {
                         info!("PDF拆分成功完成！");
@@ -96,16 +214,83 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
-        Some(("validate", sub_matches)) => {
+        Some(("detect", sub_matches)) => {
             let input_path = sub_matches.get_one::<String>("input").unwrap();
-            
-            info!("验证PDF文件: {}", input_path);
-            
-            // 这里将在后续任务中实现PDF验证逻辑
-            if Path::new(input_path).exists() {
-                info!("PDF文件存在且可访问");
+
+            info!("检测PDF文件中的章节: {}", input_path);
+
+            let chapters = pdf::detect_chapters(input_path)?;
+            println!("{}", serde_json::to_string_pretty(&chapters)?);
+        }
+        Some(("merge", sub_matches)) => {
+            let inputs: Vec<&str> = sub_matches
+                .get_many::<String>("inputs")
+                .unwrap()
+                .map(String::as_str)
+                .collect();
+            let output_path = sub_matches.get_one::<String>("output").unwrap();
+
+            info!("开始合并 {} 个PDF文件", inputs.len());
+
+            let splitter = PdfSplitter::new();
+            match splitter.merge_pdfs(&inputs, output_path) {
+                Ok(result) => {
+                    if result.success {
+                        info!("PDF合并成功完成！");
+                        info!("总页数: {}", result.total_pages);
+                    } else {
+                        error!("PDF合并失败: {:?}", result.error_message);
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    error!("PDF合并过程中发生错误: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("remove", sub_matches)) => {
+            let input_path = sub_matches.get_one::<String>("input").unwrap();
+            let output_path = sub_matches.get_one::<String>("output").unwrap();
+            let pages_spec = sub_matches.get_one::<String>("pages").unwrap();
+
+            let pages_to_drop = parse_page_ranges(pages_spec)?;
+            info!("从 {} 中删除 {} 个页面", input_path, pages_to_drop.len());
+
+            let splitter = PdfSplitter::new();
+            match splitter.remove_pages(input_path, &pages_to_drop, output_path) {
+                Ok(result) => {
+                    if result.success {
+                        info!("页面删除成功完成！");
+                        info!("剩余页数: {}", result.total_pages);
+                    } else {
+                        error!("页面删除失败: {:?}", result.error_message);
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    error!("页面删除过程中发生错误: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("validate", sub_matches)) => {
+            let input = sub_matches.get_one::<String>("input").unwrap();
+            let headers = parse_headers(sub_matches.get_many::<String>("headers"))?;
+            let as_json = sub_matches.get_flag("json");
+
+            info!("验证PDF文件: {}", input);
+
+            let (resolved_path, _temp_guard) = pdf::resolve_input(input, &headers)?;
+            let report = validator::validate_pdf(&resolved_path.to_string_lossy())?;
+
+            if as_json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
             } else {
-                error!("PDF文件不存在或无法访问");
+                println!("{}", report);
+            }
+
+            if !report.is_valid {
                 std::process::exit(1);
             }
         }
@@ -139,4 +324,53 @@ fn load_chapters_from_file(path: &str) -> anyhow::Result<Vec<ChapterDefinition>>
     }
     
     Ok(chapters)
+}
+
+/// 将重复出现的 `--header KEY=VALUE` 参数解析为键值对列表
+fn parse_headers(values: Option<clap::parser::ValuesRef<String>>) -> anyhow::Result<Vec<(String, String)>> {
+    let Some(values) = values else {
+        return Ok(Vec::new());
+    };
+
+    values
+        .map(|raw| {
+            raw.split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| anyhow::anyhow!("无效的请求头，应为 KEY=VALUE 格式: {}", raw))
+        })
+        .collect()
+}
+
+/// 解析形如 "3-5,9,12-14" 的页码范围语法为展开后的页码列表
+fn parse_page_ranges(spec: &str) -> anyhow::Result<Vec<u32>> {
+    let mut pages = Vec::new();
+
+    for part in spec.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        if let Some((start, end)) = part.split_once('-') {
+            let start: u32 = start
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("无效的页码范围: {}", part))?;
+            let end: u32 = end
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("无效的页码范围: {}", part))?;
+            if start > end {
+                return Err(anyhow::anyhow!("页码范围起始值大于结束值: {}", part));
+            }
+            pages.extend(start..=end);
+        } else {
+            let page: u32 = part
+                .parse()
+                .map_err(|_| anyhow::anyhow!("无效的页码: {}", part))?;
+            pages.push(page);
+        }
+    }
+
+    Ok(pages)
 }
\ No newline at end of file
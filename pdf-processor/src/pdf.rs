@@ -0,0 +1,546 @@
+//! PDF 底层解析工具
+//!
+//! 封装对 PDF 文档结构（页面、大纲/书签）的读取访问，以及输入来源的解析
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use crate::ChapterDefinition;
+
+static DOWNLOAD_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 临时文件的生命周期守卫，Drop时自动删除底层文件
+///
+/// 用于 [`resolve_input`] 下载的远程文件：调用方持有该守卫期间文件存在，
+/// 一旦守卫被丢弃（例如离开作用域），临时文件随即被清理。
+pub struct TempGuard {
+    path: PathBuf,
+}
+
+impl Drop for TempGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 解析 `input_path` 可能是的本地路径或远程URL
+///
+/// 如果输入以 `http://` 或 `https://` 开头，会将其下载到带有生命周期守卫的临时文件中；
+/// `s3://` 路径目前尚未实现，会明确报错而不是被误当作本地路径去查找；其它输入原样视为
+/// 本地路径返回。返回的 [`TempGuard`]（如果有）必须与使用该路径期间保持存活，一旦被丢弃
+/// 临时文件即被删除。
+pub fn resolve_input(
+    path_or_url: &str,
+    headers: &[(String, String)],
+) -> Result<(PathBuf, Option<TempGuard>)> {
+    if path_or_url.starts_with("http://") || path_or_url.starts_with("https://") {
+        let path = download_to_tempfile(path_or_url, headers)?;
+        Ok((path.clone(), Some(TempGuard { path })))
+    } else if path_or_url.starts_with("s3://") {
+        anyhow::bail!("暂不支持 S3 输入源: {}", path_or_url)
+    } else {
+        Ok((PathBuf::from(path_or_url), None))
+    }
+}
+
+/// 下载一个URL到带唯一文件名的临时文件，返回其路径
+fn download_to_tempfile(url: &str, headers: &[(String, String)]) -> Result<PathBuf> {
+    let client = reqwest::blocking::Client::new();
+    let mut request = client.get(url);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    let mut response = request
+        .send()
+        .with_context(|| format!("下载PDF文件失败: {}", url))?;
+    if !response.status().is_success() {
+        anyhow::bail!("下载PDF文件失败，HTTP状态码: {}", response.status());
+    }
+
+    let suffix = DOWNLOAD_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!("pdf-processor-{}-{}.pdf", std::process::id(), suffix));
+
+    let mut file = File::create(&temp_path)
+        .with_context(|| format!("创建临时文件失败: {}", temp_path.display()))?;
+    response
+        .copy_to(&mut file)
+        .with_context(|| format!("写入临时文件失败: {}", temp_path.display()))?;
+
+    Ok(temp_path)
+}
+
+/// 大纲树中的一个书签项
+struct Bookmark {
+    title: String,
+    page: u32,
+}
+
+/// 从 PDF 的大纲（书签）树中解析出章节定义
+///
+/// 仅读取顶层书签：书签标题成为章节标题，下一个同级书签的起始页减一
+/// 即为本章节的结束页，最后一个章节延伸到文档末页。指向页面中部的
+/// 书签仍归属于其所在页。重复或为空的标题会被去重为安全的文件名。
+/// 自身没有可解析目标的分组标题会回退到其首个子书签的目标，仍无法
+/// 解析则跳过该条目，不影响其余章节的检测。
+pub fn detect_chapters(input_path: &str) -> Result<Vec<ChapterDefinition>> {
+    let doc = Document::load(input_path)
+        .with_context(|| format!("无法打开PDF文件: {}", input_path))?;
+
+    let total_pages = doc.get_pages().len() as u32;
+    let bookmarks = top_level_bookmarks(&doc)?;
+
+    if bookmarks.is_empty() {
+        anyhow::bail!("未在「{}」中找到任何书签/大纲，无法自动检测章节", input_path);
+    }
+
+    let mut seen_titles: HashMap<String, u32> = HashMap::new();
+    let mut chapters = Vec::with_capacity(bookmarks.len());
+
+    for (i, bookmark) in bookmarks.iter().enumerate() {
+        let end_page = bookmarks
+            .get(i + 1)
+            .map(|next| next.page.saturating_sub(1).max(bookmark.page))
+            .unwrap_or(total_pages);
+
+        chapters.push(ChapterDefinition {
+            title: dedupe_title(&bookmark.title, &mut seen_titles),
+            start_page: bookmark.page,
+            end_page,
+        });
+    }
+
+    Ok(chapters)
+}
+
+/// 遍历 `/Outlines` 链表，收集顶层（第一层）书签
+fn top_level_bookmarks(doc: &Document) -> Result<Vec<Bookmark>> {
+    let catalog = doc.catalog()?;
+
+    let outlines_id = match catalog.get(b"Outlines").ok().and_then(|o| o.as_reference().ok()) {
+        Some(id) => id,
+        None => return Ok(Vec::new()),
+    };
+    let outlines = doc.get_dictionary(outlines_id)?;
+
+    let page_numbers: HashMap<ObjectId, u32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(num, id)| (id, num))
+        .collect();
+
+    let mut bookmarks = Vec::new();
+    let mut visited: HashSet<ObjectId> = HashSet::new();
+    let mut next = outlines.get(b"First").ok().and_then(|o| o.as_reference().ok());
+
+    while let Some(id) = next {
+        if !visited.insert(id) {
+            anyhow::bail!("检测到书签/大纲中存在循环引用，大纲已损坏或被篡改");
+        }
+
+        let item = doc.get_dictionary(id)?;
+        next = item.get(b"Next").ok().and_then(|o| o.as_reference().ok());
+
+        let title = item
+            .get(b"Title")
+            .ok()
+            .and_then(|o| o.as_str().ok())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+            .unwrap_or_default();
+
+        // 纯分组用的顶层标题（常见于多级目录）自身往往没有可解析的 /Dest 或 /A，
+        // 真正的目标页挂在其 /First 子书签上；仍然无法解析时跳过该条目，而不是让
+        // 整个文档的章节检测失败——孤立的标题缺失好过完全无法自动检测章节。
+        match resolve_dest_page_with_fallback(doc, item, &page_numbers) {
+            Some(page) => bookmarks.push(Bookmark { title, page }),
+            None => continue,
+        }
+    }
+
+    Ok(bookmarks)
+}
+
+/// 解析书签的目标页码，若书签自身无法解析则沿 `/First` 链下探子书签作为回退
+///
+/// 折叠目录里常见的分组标题（例如"第二部分"）本身没有 `/Dest`/`/A`，只有其首个
+/// 子书签才指向实际页面；这里依次尝试每一级的首个子项，直到解析成功或链路耗尽。
+fn resolve_dest_page_with_fallback(
+    doc: &Document,
+    item: &Dictionary,
+    page_numbers: &HashMap<ObjectId, u32>,
+) -> Option<u32> {
+    if let Some(page) = resolve_dest_page(doc, item, page_numbers) {
+        return Some(page);
+    }
+
+    let mut visited: HashSet<ObjectId> = HashSet::new();
+    let mut next_child = item.get(b"First").ok().and_then(|o| o.as_reference().ok());
+
+    while let Some(child_id) = next_child {
+        if !visited.insert(child_id) {
+            break;
+        }
+
+        let child = doc.get_dictionary(child_id).ok()?;
+        if let Some(page) = resolve_dest_page(doc, child, page_numbers) {
+            return Some(page);
+        }
+
+        next_child = child.get(b"First").ok().and_then(|o| o.as_reference().ok());
+    }
+
+    None
+}
+
+/// 从书签的 `/Dest` 或 `/A` 动作中解析出目标页码
+///
+/// `/Dest` 既可能是显式目标数组（或指向数组的间接引用），也可能是具名目标
+/// （`/Name` 或 `/String`），后者需经 [`resolve_named_dest`] 在目录中查找。
+fn resolve_dest_page(
+    doc: &Document,
+    item: &Dictionary,
+    page_numbers: &HashMap<ObjectId, u32>,
+) -> Option<u32> {
+    let dest = item.get(b"Dest").ok().cloned().or_else(|| {
+        let action = item.get(b"A").ok()?.as_reference().ok()?;
+        doc.get_dictionary(action).ok()?.get(b"D").ok().cloned()
+    })?;
+
+    let dest_array = dest_to_array(doc, &dest)?;
+    let page_ref = dest_array.first()?.as_reference().ok()?;
+    page_numbers.get(&page_ref).copied()
+}
+
+/// 将 `/Dest` 规范化为显式目标数组：数组（或指向数组的间接引用）直接使用；
+/// 具名目标（`/Name` 或 `/String`）通过 [`resolve_named_dest`] 解析
+fn dest_to_array(doc: &Document, dest: &Object) -> Option<Vec<Object>> {
+    let (_, resolved) = doc.dereference(dest).ok()?;
+    match resolved {
+        Object::Array(arr) => Some(arr.clone()),
+        Object::Name(name) => resolve_named_dest(doc, name),
+        Object::String(name, _) => resolve_named_dest(doc, name),
+        _ => None,
+    }
+}
+
+/// 在目录的 `/Names/Dests` 名称树（PDF 1.2+，如 LaTeX hyperref 生成的书签常用）或
+/// 旧式 `/Dests` 字典中查找具名目标，返回其目标数组
+fn resolve_named_dest(doc: &Document, name: &[u8]) -> Option<Vec<Object>> {
+    let catalog = doc.catalog().ok()?;
+
+    let from_name_tree = catalog
+        .get(b"Names")
+        .ok()
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_dict().ok())
+        .and_then(|names| names.get(b"Dests").ok())
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_dict().ok())
+        .and_then(|tree| search_name_tree(doc, tree, name));
+
+    let value = from_name_tree.or_else(|| {
+        catalog
+            .get(b"Dests")
+            .ok()
+            .and_then(|o| doc.dereference(o).ok())
+            .and_then(|(_, o)| o.as_dict().ok())
+            .and_then(|dests| dests.get(name).ok())
+            .cloned()
+    })?;
+
+    dest_value_to_array(doc, &value)
+}
+
+/// 名称树叶子节点的值既可能直接是目标数组，也可能是包一层 `/D` 键的字典
+fn dest_value_to_array(doc: &Document, value: &Object) -> Option<Vec<Object>> {
+    let (_, resolved) = doc.dereference(value).ok()?;
+    match resolved {
+        Object::Array(arr) => Some(arr.clone()),
+        Object::Dictionary(dict) => {
+            let d = dict.get(b"D").ok()?;
+            let (_, d_resolved) = doc.dereference(d).ok()?;
+            d_resolved.as_array().ok().cloned()
+        }
+        _ => None,
+    }
+}
+
+/// 递归搜索名称树：叶子节点的 `/Names` 是 `[key, value, key, value, ...]` 扁平数组，
+/// 中间节点的 `/Kids` 指向子节点（未按 `/Limits` 剪枝，直接线性搜索，树通常不大）
+fn search_name_tree(doc: &Document, node: &Dictionary, name: &[u8]) -> Option<Object> {
+    if let Some(names) = node
+        .get(b"Names")
+        .ok()
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_array().ok())
+    {
+        for pair in names.chunks(2) {
+            if let [key, value] = pair {
+                let matches = key.as_name().ok() == Some(name) || key.as_str().ok() == Some(name);
+                if matches {
+                    return Some(value.clone());
+                }
+            }
+        }
+    }
+
+    if let Some(kids) = node
+        .get(b"Kids")
+        .ok()
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_array().ok())
+    {
+        for kid in kids {
+            if let Ok((_, kid_obj)) = doc.dereference(kid) {
+                if let Ok(kid_dict) = kid_obj.as_dict() {
+                    if let Some(found) = search_name_tree(doc, kid_dict, name) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// 将标题去重，重复出现时追加序号，保证可以安全地用作文件名的基础
+fn dedupe_title(title: &str, seen: &mut HashMap<String, u32>) -> String {
+    let trimmed = title.trim();
+    let base = if trimmed.is_empty() {
+        "未命名章节".to_string()
+    } else {
+        trimmed.to_string()
+    };
+
+    let count = seen.entry(base.clone()).or_insert(0);
+    *count += 1;
+
+    if *count == 1 {
+        base
+    } else {
+        format!("{} ({})", base, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupe_title_appends_counter_for_repeats_and_blanks_get_placeholder() {
+        let mut seen = HashMap::new();
+        assert_eq!(dedupe_title("导言", &mut seen), "导言");
+        assert_eq!(dedupe_title("导言", &mut seen), "导言 (2)");
+        assert_eq!(dedupe_title("  ", &mut seen), "未命名章节");
+    }
+
+    #[test]
+    fn top_level_bookmarks_bails_on_cyclic_next_chain() {
+        let mut doc = Document::with_version("1.5");
+
+        let page_id = doc.new_object_id();
+        let pages_id = doc.new_object_id();
+        let mut page = Dictionary::new();
+        page.set("Type", Object::Name(b"Page".to_vec()));
+        page.set("Parent", Object::Reference(pages_id));
+        doc.set_object(page_id, Object::Dictionary(page));
+
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set("Kids", Object::Array(vec![Object::Reference(page_id)]));
+        pages.set("Count", Object::Integer(1));
+        doc.set_object(pages_id, Object::Dictionary(pages));
+
+        let dest = Object::Array(vec![Object::Reference(page_id), Object::Name(b"Fit".to_vec())]);
+
+        let item_a_id = doc.new_object_id();
+        let item_b_id = doc.new_object_id();
+
+        let mut item_a = Dictionary::new();
+        item_a.set("Title", Object::string_literal("A"));
+        item_a.set("Dest", dest.clone());
+        item_a.set("Next", Object::Reference(item_b_id));
+        doc.set_object(item_a_id, Object::Dictionary(item_a));
+
+        let mut item_b = Dictionary::new();
+        item_b.set("Title", Object::string_literal("B"));
+        item_b.set("Dest", dest);
+        item_b.set("Next", Object::Reference(item_a_id));
+        doc.set_object(item_b_id, Object::Dictionary(item_b));
+
+        let mut outlines = Dictionary::new();
+        outlines.set("First", Object::Reference(item_a_id));
+        let outlines_id = doc.add_object(Object::Dictionary(outlines));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Pages", Object::Reference(pages_id));
+        catalog.set("Outlines", Object::Reference(outlines_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        match top_level_bookmarks(&doc) {
+            Err(err) => assert!(err.to_string().contains("循环引用")),
+            Ok(_) => panic!("expected cyclic outline to be rejected"),
+        }
+    }
+
+    #[test]
+    fn top_level_bookmarks_falls_back_to_first_childs_destination() {
+        let mut doc = Document::with_version("1.5");
+
+        let page_id = doc.new_object_id();
+        let pages_id = doc.new_object_id();
+        let mut page = Dictionary::new();
+        page.set("Type", Object::Name(b"Page".to_vec()));
+        page.set("Parent", Object::Reference(pages_id));
+        doc.set_object(page_id, Object::Dictionary(page));
+
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set("Kids", Object::Array(vec![Object::Reference(page_id)]));
+        pages.set("Count", Object::Integer(1));
+        doc.set_object(pages_id, Object::Dictionary(pages));
+
+        let dest = Object::Array(vec![Object::Reference(page_id), Object::Name(b"Fit".to_vec())]);
+
+        // 子书签持有真正的目标，分组标题本身没有 /Dest
+        let child_id = doc.new_object_id();
+        let mut child = Dictionary::new();
+        child.set("Title", Object::string_literal("Section 2.1"));
+        child.set("Dest", dest);
+        doc.set_object(child_id, Object::Dictionary(child));
+
+        let group_id = doc.new_object_id();
+        let mut group = Dictionary::new();
+        group.set("Title", Object::string_literal("第二部分"));
+        group.set("First", Object::Reference(child_id));
+        doc.set_object(group_id, Object::Dictionary(group));
+
+        let mut outlines = Dictionary::new();
+        outlines.set("First", Object::Reference(group_id));
+        let outlines_id = doc.add_object(Object::Dictionary(outlines));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Pages", Object::Reference(pages_id));
+        catalog.set("Outlines", Object::Reference(outlines_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        let bookmarks = top_level_bookmarks(&doc).expect("分组标题不应导致整份文档检测失败");
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].title, "第二部分");
+        assert_eq!(bookmarks[0].page, 1);
+    }
+
+    #[test]
+    fn top_level_bookmarks_skips_entry_with_no_resolvable_destination() {
+        let mut doc = Document::with_version("1.5");
+
+        let page_id = doc.new_object_id();
+        let pages_id = doc.new_object_id();
+        let mut page = Dictionary::new();
+        page.set("Type", Object::Name(b"Page".to_vec()));
+        page.set("Parent", Object::Reference(pages_id));
+        doc.set_object(page_id, Object::Dictionary(page));
+
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set("Kids", Object::Array(vec![Object::Reference(page_id)]));
+        pages.set("Count", Object::Integer(1));
+        doc.set_object(pages_id, Object::Dictionary(pages));
+
+        let dest = Object::Array(vec![Object::Reference(page_id), Object::Name(b"Fit".to_vec())]);
+
+        // 完全没有目标（自身和子书签都没有）的条目应被跳过，而不是让整份文档检测失败
+        let unresolvable_id = doc.new_object_id();
+        let mut unresolvable = Dictionary::new();
+        unresolvable.set("Title", Object::string_literal("无目标标题"));
+        doc.set_object(unresolvable_id, Object::Dictionary(unresolvable));
+
+        let valid_id = doc.new_object_id();
+        let mut valid = Dictionary::new();
+        valid.set("Title", Object::string_literal("正常章节"));
+        valid.set("Dest", dest);
+        doc.set_object(valid_id, Object::Dictionary(valid));
+
+        let mut unresolvable_item = doc.get_dictionary(unresolvable_id).unwrap().clone();
+        unresolvable_item.set("Next", Object::Reference(valid_id));
+        doc.set_object(unresolvable_id, Object::Dictionary(unresolvable_item));
+
+        let mut outlines = Dictionary::new();
+        outlines.set("First", Object::Reference(unresolvable_id));
+        let outlines_id = doc.add_object(Object::Dictionary(outlines));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Pages", Object::Reference(pages_id));
+        catalog.set("Outlines", Object::Reference(outlines_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        let bookmarks = top_level_bookmarks(&doc).expect("无法解析的单个书签不应导致整份文档检测失败");
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].title, "正常章节");
+    }
+
+    #[test]
+    fn resolve_dest_page_follows_named_destination_through_name_tree() {
+        let mut doc = Document::with_version("1.5");
+
+        let page_id = doc.new_object_id();
+        let mut page = Dictionary::new();
+        page.set("Type", Object::Name(b"Page".to_vec()));
+        doc.set_object(page_id, Object::Dictionary(page));
+        let page_numbers: HashMap<ObjectId, u32> = [(page_id, 3u32)].into_iter().collect();
+
+        let dest_array = Object::Array(vec![Object::Reference(page_id), Object::Name(b"Fit".to_vec())]);
+
+        let mut names_leaf = Dictionary::new();
+        names_leaf.set(
+            "Names",
+            Object::Array(vec![Object::string_literal("chap1"), dest_array]),
+        );
+        let names_leaf_id = doc.add_object(Object::Dictionary(names_leaf));
+
+        let mut dests_tree = Dictionary::new();
+        dests_tree.set("Kids", Object::Array(vec![Object::Reference(names_leaf_id)]));
+        let dests_tree_id = doc.add_object(Object::Dictionary(dests_tree));
+
+        let mut names = Dictionary::new();
+        names.set("Dests", Object::Reference(dests_tree_id));
+        let names_id = doc.add_object(Object::Dictionary(names));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Names", Object::Reference(names_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        let mut bookmark = Dictionary::new();
+        bookmark.set("Title", Object::string_literal("Chapter 1"));
+        bookmark.set("Dest", Object::string_literal("chap1"));
+
+        assert_eq!(resolve_dest_page(&doc, &bookmark, &page_numbers), Some(3));
+    }
+
+    #[test]
+    fn resolve_input_passes_through_local_path_unchanged() {
+        let (path, guard) = resolve_input("/tmp/foo/bar.pdf", &[]).expect("本地路径不应报错");
+
+        assert_eq!(path, PathBuf::from("/tmp/foo/bar.pdf"));
+        assert!(guard.is_none(), "本地路径不应产生需要清理的临时文件守卫");
+    }
+
+    #[test]
+    fn resolve_input_rejects_s3_urls() {
+        match resolve_input("s3://some-bucket/chapter.pdf", &[]) {
+            Err(err) => assert!(err.to_string().contains("S3")),
+            Ok(_) => panic!("s3:// 输入应被明确拒绝"),
+        }
+    }
+}
@@ -0,0 +1,702 @@
+//! 章节拆分与文件写入
+//!
+//! 负责将页面范围提取为独立的 PDF 文件并落盘
+
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use anyhow::{Context, Result};
+use epub_builder::{EpubBuilder, EpubContent, ReferenceType, ZipLibrary};
+use lopdf::{Dictionary, Document, Object};
+
+use crate::{ChapterDefinition, ProcessResult};
+
+/// 按章节定义拆分 PDF，将每个章节写入输出目录，返回处理结果
+///
+/// 内部使用 [`split_into_files_with_progress`]，工作线程数取系统默认可用并行度，不报告进度。
+pub fn split_into_files(
+    input_path: &str,
+    chapters: &[ChapterDefinition],
+    output_dir: &str,
+) -> Result<ProcessResult> {
+    split_into_files_with_progress(input_path, chapters, output_dir, 0, |_, _, _| {})
+}
+
+/// 使用线程池并发写出各章节的PDF文件，每完成一个章节即调用一次 `on_progress`
+///
+/// `worker_count` 为 `0` 时使用 [`std::thread::available_parallelism`]。某个章节写入失败不会
+/// 中断其它章节，所有写入错误会被聚合进返回的 [`ProcessResult::error_message`] 中；
+/// 页码范围越界的章节则在写入任何文件之前直接返回 `Err`。
+pub fn split_into_files_with_progress(
+    input_path: &str,
+    chapters: &[ChapterDefinition],
+    output_dir: &str,
+    worker_count: usize,
+    mut on_progress: impl FnMut(u32, u32, &str),
+) -> Result<ProcessResult> {
+    let doc = Document::load(input_path)
+        .with_context(|| format!("无法打开PDF文件: {}", input_path))?;
+    let total_pages = doc.get_pages().len() as u32;
+
+    for chapter in chapters {
+        validate_page_range(chapter, total_pages)?;
+    }
+
+    let total = chapters.len() as u32;
+    if chapters.is_empty() {
+        return Ok(ProcessResult {
+            success: true,
+            error_message: None,
+            files_processed: 0,
+            total_pages,
+        });
+    }
+
+    let worker_count = if worker_count == 0 {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        worker_count
+    }
+    .min(chapters.len());
+
+    // 预先为每个章节分配输出文件名，避免在工作线程之间共享可变的去重状态
+    let mut seen_names: HashMap<String, u32> = HashMap::new();
+    let jobs: Vec<(ChapterDefinition, PathBuf)> = chapters
+        .iter()
+        .map(|chapter| {
+            let output_path =
+                Path::new(output_dir).join(unique_named_output(&chapter.title, "pdf", &mut seen_names));
+            (chapter.clone(), output_path)
+        })
+        .collect();
+
+    let doc = Arc::new(doc);
+    let jobs = Arc::new(Mutex::new(jobs.into_iter()));
+    let (tx, rx) = mpsc::channel::<(String, Result<()>)>();
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            let tx = tx.clone();
+            let jobs = Arc::clone(&jobs);
+            let doc = Arc::clone(&doc);
+
+            scope.spawn(move || loop {
+                let job = jobs.lock().unwrap().next();
+                let Some((chapter, output_path)) = job else {
+                    break;
+                };
+
+                // 捕获单个章节写入过程中的panic，避免其越过FFI边界或拖垮其它工作线程
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    write_page_range(&doc, chapter.start_page, chapter.end_page, &output_path)
+                }))
+                .unwrap_or_else(|payload| Err(anyhow::anyhow!("写入章节时发生panic: {}", panic_message(&payload))));
+
+                if tx.send((chapter.title.clone(), result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut processed = 0u32;
+        let mut errors = Vec::new();
+
+        for (title, result) in rx {
+            processed += 1;
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", title, e));
+            }
+            on_progress(processed, total, &title);
+        }
+
+        if errors.is_empty() {
+            Ok(ProcessResult {
+                success: true,
+                error_message: None,
+                files_processed: processed,
+                total_pages,
+            })
+        } else {
+            Ok(ProcessResult {
+                success: false,
+                error_message: Some(errors.join("; ")),
+                files_processed: processed - errors.len() as u32,
+                total_pages,
+            })
+        }
+    })
+}
+
+/// 按章节定义导出EPUB：`combine` 为 `true` 时合并为单个EPUB，否则每章一个文件
+pub fn split_into_epub(
+    input_path: &str,
+    chapters: &[ChapterDefinition],
+    output_dir: &str,
+    combine: bool,
+) -> Result<ProcessResult> {
+    let doc = Document::load(input_path)
+        .with_context(|| format!("无法打开PDF文件: {}", input_path))?;
+    let total_pages = doc.get_pages().len() as u32;
+
+    for chapter in chapters {
+        validate_page_range(chapter, total_pages)?;
+    }
+
+    let files_processed = if combine {
+        let book_title = Path::new(input_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "book".to_string());
+        let output_path = Path::new(output_dir).join(format!("{}.epub", sanitize_filename(&book_title)));
+        write_combined_epub(&doc, &book_title, chapters, &output_path)?;
+        chapters.len() as u32
+    } else {
+        let mut seen_names: HashMap<String, u32> = HashMap::new();
+        let mut files_processed = 0u32;
+
+        for chapter in chapters {
+            let output_path =
+                Path::new(output_dir).join(unique_named_output(&chapter.title, "epub", &mut seen_names));
+            write_single_chapter_epub(&doc, chapter, &output_path)?;
+            files_processed += 1;
+        }
+
+        files_processed
+    };
+
+    Ok(ProcessResult {
+        success: true,
+        error_message: None,
+        files_processed,
+        total_pages,
+    })
+}
+
+/// 从 `catch_unwind` 捕获的panic负载中提取可读的错误信息
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知panic".to_string()
+    }
+}
+
+/// 校验章节的页码范围是否落在文档范围内
+fn validate_page_range(chapter: &ChapterDefinition, total_pages: u32) -> Result<()> {
+    if chapter.start_page == 0 || chapter.start_page > chapter.end_page || chapter.end_page > total_pages
+    {
+        anyhow::bail!(
+            "章节「{}」的页码范围 {}-{} 超出文档范围(共{}页)",
+            chapter.title,
+            chapter.start_page,
+            chapter.end_page,
+            total_pages
+        );
+    }
+    Ok(())
+}
+
+/// 将 `[start_page, end_page]`（含端点，从1开始）之外的页面删除并保存为新文件
+fn write_page_range(doc: &Document, start_page: u32, end_page: u32, output_path: &Path) -> Result<()> {
+    let mut chapter_doc = doc.clone();
+    let total_pages = chapter_doc.get_pages().len() as u32;
+
+    let pages_to_remove: Vec<u32> = (1..=total_pages)
+        .filter(|page| *page < start_page || *page > end_page)
+        .collect();
+    chapter_doc.delete_pages(&pages_to_remove);
+    chapter_doc.prune_objects();
+
+    chapter_doc
+        .save(output_path)
+        .with_context(|| format!("写入章节文件失败: {}", output_path.display()))?;
+
+    Ok(())
+}
+
+/// 提取页面范围内的纯文本内容
+fn extract_chapter_text(doc: &Document, start_page: u32, end_page: u32) -> Result<String> {
+    let page_numbers: Vec<u32> = (start_page..=end_page).collect();
+    doc.extract_text(&page_numbers)
+        .with_context(|| format!("提取第{}-{}页文本失败", start_page, end_page))
+}
+
+/// `epub_builder` 使用 `eyre::Report` 作为错误类型，与 `anyhow` 不互通，这里统一转换
+fn epub_err(e: impl std::fmt::Display) -> anyhow::Error {
+    anyhow::anyhow!("{}", e)
+}
+
+/// 将单章内容写入独立的EPUB文件
+fn write_single_chapter_epub(doc: &Document, chapter: &ChapterDefinition, output_path: &Path) -> Result<()> {
+    let text = extract_chapter_text(doc, chapter.start_page, chapter.end_page)?;
+
+    let mut epub = EpubBuilder::new(ZipLibrary::new().map_err(epub_err)?).map_err(epub_err)?;
+    epub.metadata("title", chapter.title.clone()).map_err(epub_err)?;
+    epub.add_content(
+        EpubContent::new("chapter.xhtml", chapter_xhtml(&chapter.title, &text).as_bytes())
+            .title(chapter.title.clone())
+            .reftype(ReferenceType::Text),
+    )
+    .map_err(epub_err)?;
+
+    let file = File::create(output_path)
+        .with_context(|| format!("创建EPUB文件失败: {}", output_path.display()))?;
+    epub.generate(file)
+        .map_err(epub_err)
+        .with_context(|| format!("写入EPUB文件失败: {}", output_path.display()))?;
+
+    Ok(())
+}
+
+/// 将所有章节合并为单个EPUB，使用章节标题生成导航目录(nav/TOC)
+fn write_combined_epub(
+    doc: &Document,
+    book_title: &str,
+    chapters: &[ChapterDefinition],
+    output_path: &Path,
+) -> Result<()> {
+    let mut epub = EpubBuilder::new(ZipLibrary::new().map_err(epub_err)?).map_err(epub_err)?;
+    epub.metadata("title", book_title.to_string()).map_err(epub_err)?;
+
+    for (i, chapter) in chapters.iter().enumerate() {
+        let text = extract_chapter_text(doc, chapter.start_page, chapter.end_page)?;
+        let section_path = format!("chapter_{}.xhtml", i + 1);
+
+        epub.add_content(
+            EpubContent::new(section_path, chapter_xhtml(&chapter.title, &text).as_bytes())
+                .title(chapter.title.clone())
+                .reftype(ReferenceType::Text),
+        )
+        .map_err(epub_err)?;
+    }
+
+    let file = File::create(output_path)
+        .with_context(|| format!("创建EPUB文件失败: {}", output_path.display()))?;
+    epub.generate(file)
+        .map_err(epub_err)
+        .with_context(|| format!("写入EPUB文件失败: {}", output_path.display()))?;
+
+    Ok(())
+}
+
+/// 将纯文本章节内容包装为最小化的XHTML
+fn chapter_xhtml(title: &str, text: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\"><head><title>{title}</title></head>\n\
+         <body><h1>{title}</h1><pre>{body}</pre></body></html>",
+        title = escape_xhtml(title),
+        body = escape_xhtml(text),
+    )
+}
+
+/// 转义XHTML中的特殊字符
+fn escape_xhtml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// 将标题转换为安全的文件名，重复的标题追加序号以避免覆盖
+pub fn unique_filename(title: &str, seen: &mut HashMap<String, u32>) -> String {
+    unique_named_output(title, "pdf", seen)
+}
+
+/// 将标题转换为带指定扩展名的安全文件名，重复的标题追加序号以避免覆盖
+fn unique_named_output(title: &str, extension: &str, seen: &mut HashMap<String, u32>) -> String {
+    let safe_title = sanitize_filename(title);
+    let count = seen.entry(safe_title.clone()).or_insert(0);
+    *count += 1;
+
+    if *count == 1 {
+        format!("{}.{}", safe_title, extension)
+    } else {
+        format!("{}_{}.{}", safe_title, count, extension)
+    }
+}
+
+/// 去除文件系统中的非法字符，空标题回退为 "untitled"
+fn sanitize_filename(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let trimmed = cleaned.trim();
+    if trimmed.is_empty() {
+        "untitled".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// 按顺序合并多个PDF文件为一个文件，返回处理结果（`total_pages` 为合并后的总页数）
+pub fn merge_pdfs(inputs: &[&str], output_path: &str) -> Result<ProcessResult> {
+    if inputs.is_empty() {
+        anyhow::bail!("至少需要提供一个输入文件才能合并");
+    }
+
+    let mut merged = merge_documents(inputs)?;
+    let total_pages = merged.get_pages().len() as u32;
+
+    merged
+        .save(output_path)
+        .with_context(|| format!("写入合并后的PDF文件失败: {}", output_path))?;
+
+    Ok(ProcessResult {
+        success: true,
+        error_message: None,
+        files_processed: 1,
+        total_pages,
+    })
+}
+
+/// 将多个PDF文档的对象重新编号后拼接为一个新文档，保持页面顺序
+fn merge_documents(inputs: &[&str]) -> Result<Document> {
+    let mut max_id: u32 = 1;
+    // 按页码顺序收集页面（而非按对象ID排序），以保持各输入文件原有的页面顺序
+    let mut documents_pages: Vec<((u32, u16), Object)> = Vec::new();
+    let mut documents_objects: BTreeMap<(u32, u16), Object> = BTreeMap::new();
+    let mut first_catalog: Option<Dictionary> = None;
+
+    for input in inputs {
+        let mut doc = Document::load(input)
+            .with_context(|| format!("无法打开PDF文件: {}", input))?;
+        doc.renumber_objects_with(max_id);
+        max_id = doc.max_id + 1;
+
+        if first_catalog.is_none() {
+            first_catalog = doc.catalog().ok().cloned();
+        }
+
+        documents_pages.extend(
+            doc.get_pages()
+                .into_values()
+                .filter_map(|object_id| doc.get_object(object_id).ok().map(|obj| (object_id, obj.clone()))),
+        );
+        documents_objects.extend(doc.objects.clone());
+    }
+
+    let mut document = Document::with_version("1.5");
+    document.objects = documents_objects;
+    document.max_id = document.objects.keys().map(|(id, _)| *id).max().unwrap_or(0);
+
+    let pages_id = document.new_object_id();
+    for (object_id, object) in &documents_pages {
+        if let Ok(dict) = object.as_dict() {
+            let mut dict = dict.clone();
+            dict.set("Parent", Object::Reference(pages_id));
+            document.objects.insert(*object_id, Object::Dictionary(dict));
+        }
+    }
+
+    let mut pages_dict = Dictionary::new();
+    pages_dict.set("Type", Object::Name(b"Pages".to_vec()));
+    pages_dict.set(
+        "Kids",
+        Object::Array(documents_pages.iter().map(|(id, _)| Object::Reference(*id)).collect()),
+    );
+    pages_dict.set("Count", Object::Integer(documents_pages.len() as i64));
+    document.objects.insert(pages_id, Object::Dictionary(pages_dict));
+
+    let mut catalog = first_catalog.unwrap_or_default();
+    catalog.set("Pages", Object::Reference(pages_id));
+    catalog.remove(b"Outlines");
+    let catalog_id = document.add_object(Object::Dictionary(catalog));
+
+    document.trailer.set("Root", Object::Reference(catalog_id));
+    document.renumber_objects();
+    document.compress();
+
+    Ok(document)
+}
+
+/// 从PDF中删除给定的（1-based）页码，写出一个新文件；即"保留除这些页之外的所有页"
+pub fn remove_pages(input_path: &str, pages_to_drop: &[u32], output_path: &str) -> Result<ProcessResult> {
+    let mut doc = Document::load(input_path)
+        .with_context(|| format!("无法打开PDF文件: {}", input_path))?;
+    let total_pages = doc.get_pages().len() as u32;
+
+    let unique_drops: BTreeSet<u32> = pages_to_drop.iter().copied().collect();
+    for &page in &unique_drops {
+        if page == 0 || page > total_pages {
+            anyhow::bail!("页码 {} 超出文档范围(共{}页)", page, total_pages);
+        }
+    }
+
+    let drop_vec: Vec<u32> = unique_drops.iter().copied().collect();
+    doc.delete_pages(&drop_vec);
+    doc.prune_objects();
+
+    doc.save(output_path)
+        .with_context(|| format!("写入PDF文件失败: {}", output_path))?;
+
+    Ok(ProcessResult {
+        success: true,
+        error_message: None,
+        files_processed: 1,
+        total_pages: total_pages - unique_drops.len() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static TEST_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    /// 构建一个含 `page_count` 页的最小PDF，每页携带 `marker` 作为自定义标记，
+    /// 写入进程内唯一的临时文件并返回其路径；调用方负责在用完后删除
+    fn write_marked_pdf(label: &str, page_count: u32, marker: i64) -> PathBuf {
+        let mut doc = Document::with_version("1.5");
+
+        let pages_id = doc.new_object_id();
+        let mut page_ids = Vec::new();
+        for _ in 0..page_count {
+            let mut page = Dictionary::new();
+            page.set("Type", Object::Name(b"Page".to_vec()));
+            page.set("Parent", Object::Reference(pages_id));
+            page.set("TestMarker", Object::Integer(marker));
+            page_ids.push(doc.add_object(Object::Dictionary(page)));
+        }
+
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set(
+            "Kids",
+            Object::Array(page_ids.iter().map(|id| Object::Reference(*id)).collect()),
+        );
+        pages.set("Count", Object::Integer(page_ids.len() as i64));
+        doc.set_object(pages_id, Object::Dictionary(pages));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+        catalog.set("Pages", Object::Reference(pages_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        let suffix = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = std::env::temp_dir();
+        path.push(format!("pdf-processor-splitter-test-{}-{}-{}.pdf", std::process::id(), label, suffix));
+        doc.save(&path).expect("写入测试临时文件失败");
+        path
+    }
+
+    /// 读出文档各页（按页码顺序）上的 `TestMarker` 标记
+    fn page_markers(doc: &Document) -> Vec<i64> {
+        doc.get_pages()
+            .into_values()
+            .map(|id| {
+                doc.get_dictionary(id)
+                    .ok()
+                    .and_then(|d| d.get(b"TestMarker").ok())
+                    .and_then(|o| o.as_i64().ok())
+                    .expect("测试页面缺少TestMarker")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_documents_preserves_input_order_across_files() {
+        let path_a = write_marked_pdf("merge-a", 2, 1);
+        let path_b = write_marked_pdf("merge-b", 1, 2);
+        let path_a_str = path_a.to_string_lossy().into_owned();
+        let path_b_str = path_b.to_string_lossy().into_owned();
+
+        let merged = merge_documents(&[&path_a_str, &path_b_str]).expect("合并不应失败");
+
+        let _ = std::fs::remove_file(&path_a);
+        let _ = std::fs::remove_file(&path_b);
+
+        assert_eq!(page_markers(&merged), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn merge_pdfs_reports_total_pages_as_sum_of_inputs() {
+        let path_a = write_marked_pdf("merge-sum-a", 2, 1);
+        let path_b = write_marked_pdf("merge-sum-b", 3, 2);
+        let path_a_str = path_a.to_string_lossy().into_owned();
+        let path_b_str = path_b.to_string_lossy().into_owned();
+
+        let out_suffix = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut out_path = std::env::temp_dir();
+        out_path.push(format!("pdf-processor-splitter-test-{}-merge-out-{}.pdf", std::process::id(), out_suffix));
+
+        let result = merge_pdfs(&[&path_a_str, &path_b_str], &out_path.to_string_lossy()).expect("合并不应失败");
+
+        let _ = std::fs::remove_file(&path_a);
+        let _ = std::fs::remove_file(&path_b);
+        let _ = std::fs::remove_file(&out_path);
+
+        assert_eq!(result.total_pages, 5);
+    }
+
+    #[test]
+    fn remove_pages_rejects_page_number_out_of_bounds() {
+        let input_path = write_marked_pdf("remove-bounds", 3, 1);
+        let input_path_str = input_path.to_string_lossy().into_owned();
+
+        let out_suffix = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut out_path = std::env::temp_dir();
+        out_path.push(format!("pdf-processor-splitter-test-{}-remove-out-{}.pdf", std::process::id(), out_suffix));
+
+        let err = remove_pages(&input_path_str, &[5], &out_path.to_string_lossy()).unwrap_err();
+
+        let _ = std::fs::remove_file(&input_path);
+        let removed_anyway = out_path.exists();
+        let _ = std::fs::remove_file(&out_path);
+
+        assert!(err.to_string().contains("超出文档范围"));
+        assert!(!removed_anyway, "校验失败时不应写出任何文件");
+    }
+
+    /// 分配一个进程内唯一的临时目录并创建它，调用方负责在用完后删除
+    fn temp_dir(label: &str) -> PathBuf {
+        let suffix = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = std::env::temp_dir();
+        path.push(format!("pdf-processor-splitter-test-{}-{}-{}", std::process::id(), label, suffix));
+        std::fs::create_dir_all(&path).expect("创建测试临时目录失败");
+        path
+    }
+
+    #[test]
+    fn split_into_files_with_progress_writes_one_file_per_chapter_with_multiple_workers() {
+        let input_path = write_marked_pdf("split-progress-ok", 4, 1);
+        let input_path_str = input_path.to_string_lossy().into_owned();
+        let output_dir = temp_dir("split-progress-ok-out");
+
+        let chapters = vec![
+            ChapterDefinition { title: "第一章".to_string(), start_page: 1, end_page: 2 },
+            ChapterDefinition { title: "第二章".to_string(), start_page: 3, end_page: 4 },
+        ];
+
+        let progress_calls = Arc::new(Mutex::new(Vec::new()));
+        let progress_calls_clone = Arc::clone(&progress_calls);
+        let result = split_into_files_with_progress(
+            &input_path_str,
+            &chapters,
+            &output_dir.to_string_lossy(),
+            2,
+            |processed, total, title| {
+                progress_calls_clone.lock().unwrap().push((processed, total, title.to_string()));
+            },
+        )
+        .expect("拆分不应失败");
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        assert!(result.success);
+        assert_eq!(result.files_processed, 2);
+
+        let calls = progress_calls.lock().unwrap();
+        assert_eq!(calls.len(), 2, "进度回调应每个章节恰好触发一次");
+        let titles: BTreeSet<String> = calls.iter().map(|(_, _, title)| title.clone()).collect();
+        assert_eq!(titles, BTreeSet::from(["第一章".to_string(), "第二章".to_string()]));
+        assert!(calls.iter().all(|(_, total, _)| *total == 2));
+    }
+
+    #[test]
+    fn split_into_files_with_progress_rejects_invalid_chapter_range_before_writing() {
+        let input_path = write_marked_pdf("split-progress-err", 2, 1);
+        let input_path_str = input_path.to_string_lossy().into_owned();
+        let output_dir = temp_dir("split-progress-err-out");
+
+        let chapters = vec![ChapterDefinition { title: "越界章节".to_string(), start_page: 1, end_page: 5 }];
+
+        let result = split_into_files_with_progress(
+            &input_path_str,
+            &chapters,
+            &output_dir.to_string_lossy(),
+            2,
+            |_, _, _| {},
+        );
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("越界章节"));
+    }
+
+    #[test]
+    fn chapter_xhtml_escapes_reserved_characters() {
+        let html = chapter_xhtml("A & B <Title>", "x < y & y > z");
+
+        assert!(html.contains("A &amp; B &lt;Title&gt;"));
+        assert!(html.contains("x &lt; y &amp; y &gt; z"));
+        assert!(!html.contains("A & B <Title>"));
+    }
+
+    #[test]
+    fn split_into_epub_writes_one_file_per_chapter_when_not_combined() {
+        let input_path = write_marked_pdf("epub-split", 2, 1);
+        let input_path_str = input_path.to_string_lossy().into_owned();
+        let output_dir = temp_dir("epub-split-out");
+
+        let chapters = vec![ChapterDefinition { title: "第一章".to_string(), start_page: 1, end_page: 1 }];
+        let result = split_into_epub(&input_path_str, &chapters, &output_dir.to_string_lossy(), false)
+            .expect("拆分不应失败");
+
+        let epub_path = output_dir.join("第一章.epub");
+        let epub_exists = epub_path.exists();
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        assert!(result.success);
+        assert_eq!(result.files_processed, 1);
+        assert!(epub_exists, "应在输出目录生成以章节标题命名的EPUB文件");
+    }
+
+    #[test]
+    fn split_into_epub_combines_all_chapters_into_one_file_with_correct_chapter_count() {
+        let input_path = write_marked_pdf("epub-combine", 2, 1);
+        let input_path_str = input_path.to_string_lossy().into_owned();
+        let output_dir = temp_dir("epub-combine-out");
+
+        let chapters = vec![
+            ChapterDefinition { title: "第一章".to_string(), start_page: 1, end_page: 1 },
+            ChapterDefinition { title: "第二章".to_string(), start_page: 2, end_page: 2 },
+        ];
+        let result = split_into_epub(&input_path_str, &chapters, &output_dir.to_string_lossy(), true)
+            .expect("拆分不应失败");
+
+        let stem = Path::new(&input_path_str).file_stem().unwrap().to_string_lossy().into_owned();
+        let epub_path = output_dir.join(format!("{}.epub", stem));
+        let archive = File::open(&epub_path).ok().and_then(|f| zip::ZipArchive::new(f).ok());
+        let chapter_entry_count = archive.map(|mut archive| {
+            (0..archive.len())
+                .filter(|&i| {
+                    archive
+                        .by_index(i)
+                        .map(|entry| entry.name().contains("chapter_") && entry.name().ends_with(".xhtml"))
+                        .unwrap_or(false)
+                })
+                .count()
+        });
+
+        let _ = std::fs::remove_file(&input_path);
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        assert!(result.success);
+        assert_eq!(result.files_processed, 2);
+        assert_eq!(chapter_entry_count, Some(2), "合并后的EPUB应恰好包含两个章节xhtml条目");
+    }
+}
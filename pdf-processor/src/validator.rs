@@ -0,0 +1,294 @@
+//! PDF 文件验证
+//!
+//! 检查 `%PDF-` 文件头与 `%%EOF` 结束标记、统计页数、检测加密状态，
+//! 并标记损坏或截断的 xref 表
+
+use std::fmt;
+use std::fs;
+
+use anyhow::{Context, Result};
+use lopdf::Document;
+use serde::{Deserialize, Serialize};
+
+use crate::ChapterDefinition;
+
+/// PDF验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// 文件是否通过基础有效性检查
+    pub is_valid: bool,
+    /// 页面总数
+    pub page_count: u32,
+    /// 是否加密
+    pub is_encrypted: bool,
+    /// PDF版本号，如 "1.7"；无法识别时为空字符串
+    pub version: String,
+    /// 非致命性问题列表
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "有效: {}", self.is_valid)?;
+        writeln!(f, "PDF版本: {}", if self.version.is_empty() { "未知" } else { &self.version })?;
+        writeln!(f, "页数: {}", self.page_count)?;
+        writeln!(f, "已加密: {}", self.is_encrypted)?;
+        if self.warnings.is_empty() {
+            write!(f, "警告: 无")
+        } else {
+            writeln!(f, "警告:")?;
+            for (i, warning) in self.warnings.iter().enumerate() {
+                if i + 1 == self.warnings.len() {
+                    write!(f, "  - {}", warning)?;
+                } else {
+                    writeln!(f, "  - {}", warning)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 验证PDF文件：检查文件头/尾标记、统计页数、检测加密状态，并标记损坏的xref表
+pub fn validate_pdf(path: &str) -> Result<ValidationReport> {
+    let bytes = fs::read(path).with_context(|| format!("无法读取文件: {}", path))?;
+
+    let version = match extract_version(&bytes) {
+        Some(version) => version,
+        None => {
+            return Ok(ValidationReport {
+                is_valid: false,
+                page_count: 0,
+                is_encrypted: false,
+                version: String::new(),
+                warnings: vec!["文件缺少有效的 %PDF- 文件头，不是合法的PDF文件".to_string()],
+            });
+        }
+    };
+
+    let mut warnings = Vec::new();
+    if !has_eof_trailer(&bytes) {
+        warnings.push("文件缺少 %%EOF 结束标记，文件可能已被截断".to_string());
+    }
+
+    let doc = match Document::load(path) {
+        Ok(doc) => doc,
+        Err(e) => {
+            warnings.push(format!("无法解析xref表，文档可能已损坏: {}", e));
+            return Ok(ValidationReport {
+                is_valid: false,
+                page_count: 0,
+                is_encrypted: false,
+                version,
+                warnings,
+            });
+        }
+    };
+
+    let page_count = doc.get_pages().len() as u32;
+    if page_count == 0 {
+        warnings.push("文档不包含任何页面".to_string());
+    }
+
+    let is_encrypted = doc.is_encrypted();
+    if is_encrypted {
+        warnings.push("文档已加密，部分操作可能受限".to_string());
+    }
+
+    Ok(ValidationReport {
+        is_valid: page_count > 0,
+        page_count,
+        is_encrypted,
+        version,
+        warnings,
+    })
+}
+
+/// 拆分前的预检：校验PDF本身有效，并确保各章节的页码范围不超出文档范围
+///
+/// 供 [`crate::PdfSplitter::split_pdf`] 与FFI的 `split_pdf_chapters_with_progress` 共用，
+/// 避免损坏/加密异常或页码越界的输入被悄悄拆成空文件或损坏输出，而不是在某一条路径上才拦截
+pub fn preflight_chapters(input_path: &str, chapters: &[ChapterDefinition]) -> Result<ValidationReport> {
+    let report = validate_pdf(input_path)?;
+    if !report.is_valid {
+        anyhow::bail!("PDF文件验证未通过，无法拆分: {}", report.warnings.join("; "));
+    }
+
+    for chapter in chapters {
+        if chapter.end_page > report.page_count {
+            anyhow::bail!(
+                "章节「{}」引用了第{}页，但文档仅有{}页",
+                chapter.title,
+                chapter.end_page,
+                report.page_count
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+/// 从文件头附近查找 `%PDF-x.y` 标记并提取版本号
+fn extract_version(bytes: &[u8]) -> Option<String> {
+    let header_len = bytes.len().min(1024);
+    let text = String::from_utf8_lossy(&bytes[..header_len]);
+
+    let marker = "%PDF-";
+    let start = text.find(marker)? + marker.len();
+    let version: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+/// 检查文件末尾附近是否存在 `%%EOF` 标记
+fn has_eof_trailer(bytes: &[u8]) -> bool {
+    let tail_start = bytes.len().saturating_sub(1024);
+    let text = String::from_utf8_lossy(&bytes[tail_start..]);
+    text.contains("%%EOF")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{Dictionary, Document, Object};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static TEST_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    /// 构建一个只含若干空白页的最小合法PDF，返回其序列化字节
+    fn minimal_pdf_bytes(page_count: u32, encrypted: bool) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+
+        let pages_id = doc.new_object_id();
+        let mut page_ids = Vec::new();
+        for _ in 0..page_count {
+            let mut page = Dictionary::new();
+            page.set("Type", Object::Name(b"Page".to_vec()));
+            page.set("Parent", Object::Reference(pages_id));
+            page_ids.push(doc.add_object(Object::Dictionary(page)));
+        }
+
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set(
+            "Kids",
+            Object::Array(page_ids.iter().map(|id| Object::Reference(*id)).collect()),
+        );
+        pages.set("Count", Object::Integer(page_ids.len() as i64));
+        doc.set_object(pages_id, Object::Dictionary(pages));
+
+        let mut catalog = Dictionary::new();
+        catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+        catalog.set("Pages", Object::Reference(pages_id));
+        let catalog_id = doc.add_object(Object::Dictionary(catalog));
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        if encrypted {
+            let mut encrypt = Dictionary::new();
+            encrypt.set("Filter", Object::Name(b"Standard".to_vec()));
+            let encrypt_id = doc.add_object(Object::Dictionary(encrypt));
+            doc.trailer.set("Encrypt", Object::Reference(encrypt_id));
+        }
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).expect("序列化测试用PDF失败");
+        bytes
+    }
+
+    /// 将字节写入一个进程内唯一的临时文件，返回其路径；调用方负责在用完后删除
+    fn write_temp_pdf(label: &str, bytes: &[u8]) -> PathBuf {
+        let suffix = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = std::env::temp_dir();
+        path.push(format!("pdf-processor-validator-test-{}-{}-{}.pdf", std::process::id(), label, suffix));
+        fs::write(&path, bytes).expect("写入测试临时文件失败");
+        path
+    }
+
+    #[test]
+    fn validate_pdf_reports_valid_unencrypted_document() {
+        let bytes = minimal_pdf_bytes(2, false);
+        let path = write_temp_pdf("valid", &bytes);
+
+        let report = validate_pdf(&path.to_string_lossy()).expect("validate_pdf不应返回Err");
+        let _ = fs::remove_file(&path);
+
+        assert!(report.is_valid);
+        assert_eq!(report.page_count, 2);
+        assert!(!report.is_encrypted);
+        assert_eq!(report.version, "1.5");
+    }
+
+    #[test]
+    fn validate_pdf_detects_encryption() {
+        let bytes = minimal_pdf_bytes(1, true);
+        let path = write_temp_pdf("encrypted", &bytes);
+
+        let report = validate_pdf(&path.to_string_lossy()).expect("validate_pdf不应返回Err");
+        let _ = fs::remove_file(&path);
+
+        assert!(report.is_encrypted);
+        assert!(report.warnings.iter().any(|w| w.contains("加密")));
+    }
+
+    #[test]
+    fn validate_pdf_flags_missing_header() {
+        let path = write_temp_pdf("no-header", b"this is not a pdf file at all");
+
+        let report = validate_pdf(&path.to_string_lossy()).expect("validate_pdf不应返回Err");
+        let _ = fs::remove_file(&path);
+
+        assert!(!report.is_valid);
+        assert!(report.warnings.iter().any(|w| w.contains("%PDF-")));
+    }
+
+    #[test]
+    fn validate_pdf_flags_missing_eof_trailer() {
+        let mut bytes = minimal_pdf_bytes(1, false);
+        assert!(bytes.ends_with(b"%%EOF"));
+        bytes.truncate(bytes.len() - b"%%EOF".len());
+        let path = write_temp_pdf("no-eof", &bytes);
+
+        let report = validate_pdf(&path.to_string_lossy()).expect("validate_pdf不应返回Err");
+        let _ = fs::remove_file(&path);
+
+        assert!(report.warnings.iter().any(|w| w.contains("%%EOF")));
+    }
+
+    #[test]
+    fn validate_pdf_flags_corrupted_xref_table() {
+        let bytes = minimal_pdf_bytes(1, false);
+        let truncated = &bytes[..bytes.len() / 2];
+        let path = write_temp_pdf("truncated-xref", truncated);
+
+        let report = validate_pdf(&path.to_string_lossy()).expect("validate_pdf不应返回Err");
+        let _ = fs::remove_file(&path);
+
+        assert!(!report.is_valid);
+        assert_eq!(report.page_count, 0);
+        assert!(report.warnings.iter().any(|w| w.contains("xref")));
+    }
+
+    #[test]
+    fn preflight_chapters_rejects_out_of_bounds_chapter() {
+        let bytes = minimal_pdf_bytes(2, false);
+        let path = write_temp_pdf("preflight", &bytes);
+
+        let chapters = vec![ChapterDefinition {
+            title: "超界章节".to_string(),
+            start_page: 1,
+            end_page: 5,
+        }];
+        let err = preflight_chapters(&path.to_string_lossy(), &chapters).unwrap_err();
+        let _ = fs::remove_file(&path);
+
+        assert!(err.to_string().contains("超界章节"));
+    }
+}